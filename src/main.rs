@@ -1,11 +1,18 @@
 use chrono::{Local, NaiveDateTime, TimeZone, Utc};
 use clap::{Arg, Command};
 use exif::{In, Reader, Tag, Value};
+use filetime::FileTime;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde_json::Value as JsonValue;
+use sha2::{Digest, Sha256};
 use std::fs::{metadata, File};
-use std::io::BufReader;
-use std::path::Path;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-fn format_exif_date(file_path: &Path, date_format: &str) -> Option<String> {
+fn parse_exif_date(file_path: &Path) -> Option<NaiveDateTime> {
     // Open the file and prepare it for EXIF metadata reading
     let file = File::open(file_path).ok()?;
     let mut bufreader = BufReader::new(file);
@@ -24,8 +31,7 @@ fn format_exif_date(file_path: &Path, date_format: &str) -> Option<String> {
                 if let Ok(parsed_date) =
                     NaiveDateTime::parse_from_str(&exif_date_str, "%Y:%m:%d %H:%M:%S")
                 {
-                    // Format the parsed date into the user-specified format
-                    return Some(parsed_date.format(date_format).to_string());
+                    return Some(parsed_date);
                 }
             }
         }
@@ -34,12 +40,9 @@ fn format_exif_date(file_path: &Path, date_format: &str) -> Option<String> {
     None
 }
 
-fn format_modified_date(file_path: &Path, date_format: &str) -> Option<String> {
-    let metadata = metadata(file_path).ok()?;
-    let modified_time = metadata.modified().ok()?;
-
+fn system_time_to_datetime(time: std::time::SystemTime) -> Option<NaiveDateTime> {
     // Convert the system time to a UNIX timestamp
-    let timestamp = modified_time
+    let timestamp = time
         .duration_since(std::time::UNIX_EPOCH)
         .ok()?
         .as_secs() as i64;
@@ -47,20 +50,113 @@ fn format_modified_date(file_path: &Path, date_format: &str) -> Option<String> {
     // Convert the UNIX timestamp to a DateTime<Utc>
     let date_time_utc = Utc.timestamp_opt(timestamp, 0).single()?;
 
-    // Convert DateTime<Utc> to Local DateTime
-    let local_date_time = date_time_utc.with_timezone(&Local);
+    // Convert DateTime<Utc> to a local naive datetime
+    Some(date_time_utc.with_timezone(&Local).naive_local())
+}
 
-    // Format the date into the user-specified format
-    Some(local_date_time.format(date_format).to_string())
+fn modified_datetime(file_path: &Path) -> Option<NaiveDateTime> {
+    let metadata = metadata(file_path).ok()?;
+    let modified_time = metadata.modified().ok()?;
+    system_time_to_datetime(modified_time)
 }
 
-fn get_formatted_date(file_path: &Path, date_format: &str) -> Option<String> {
-    if let Some(formatted_date) = format_exif_date(file_path, date_format) {
-        Some(formatted_date)
-    } else if let Some(modified_date) = format_modified_date(file_path, date_format) {
-        Some(modified_date)
+fn created_datetime(file_path: &Path) -> Option<NaiveDateTime> {
+    let metadata = metadata(file_path).ok()?;
+    // Birth time isn't available on every platform; fall through on error.
+    let created_time = metadata.created().ok()?;
+    system_time_to_datetime(created_time)
+}
+
+fn accessed_datetime(file_path: &Path) -> Option<NaiveDateTime> {
+    let metadata = metadata(file_path).ok()?;
+    let accessed_time = metadata.accessed().ok()?;
+    system_time_to_datetime(accessed_time)
+}
+
+/// Shell out to the external `exiftool` binary for formats the pure-Rust
+/// reader can't handle (HEIC, many RAW formats, MOV/MP4). Parses the
+/// `CreateDate` field out of the `-j` (JSON) output.
+fn exiftool_datetime(file_path: &Path) -> Option<NaiveDateTime> {
+    let output = std::process::Command::new("exiftool")
+        .arg("-j")
+        .arg("-CreateDate")
+        .arg(file_path)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    // exiftool emits a JSON array with one object per file.
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout).ok()?;
+    let create_date = parsed.get(0)?.get("CreateDate")?.as_str()?;
+
+    NaiveDateTime::parse_from_str(create_date, "%Y:%m:%d %H:%M:%S").ok()
+}
+
+/// The source that ultimately supplied a file's date, for verbose reporting.
+#[derive(Clone, Copy)]
+enum DateSource {
+    Exif,
+    Exiftool,
+    Created,
+    Accessed,
+    Modified,
+}
+
+impl DateSource {
+    fn label(self) -> &'static str {
+        match self {
+            DateSource::Exif => "EXIF",
+            DateSource::Exiftool => "exiftool",
+            DateSource::Created => "created",
+            DateSource::Accessed => "accessed",
+            DateSource::Modified => "modified",
+        }
+    }
+}
+
+fn get_formatted_date(
+    file_path: &Path,
+    use_exiftool: bool,
+) -> Option<(NaiveDateTime, DateSource)> {
+    // Fallback order: EXIF → exiftool (opt-in) → modified time.
+    if let Some(datetime) = parse_exif_date(file_path) {
+        Some((datetime, DateSource::Exif))
+    } else if use_exiftool {
+        if let Some(datetime) = exiftool_datetime(file_path) {
+            Some((datetime, DateSource::Exiftool))
+        } else {
+            modified_datetime(file_path).map(|datetime| (datetime, DateSource::Modified))
+        }
     } else {
-        None
+        modified_datetime(file_path).map(|datetime| (datetime, DateSource::Modified))
+    }
+}
+
+/// Resolve a file's date and the source it came from, honouring the selected
+/// date-source flag. Returns the raw `NaiveDateTime` so callers can format it
+/// however they need (user format for the name, `YYYY/MM` for the library
+/// tree) without re-reading the file.
+fn resolve_date(
+    file_path: &Path,
+    exif_only: bool,
+    modified_only: bool,
+    created_only: bool,
+    accessed_only: bool,
+    use_exiftool: bool,
+) -> Option<(NaiveDateTime, DateSource)> {
+    if exif_only {
+        parse_exif_date(file_path).map(|datetime| (datetime, DateSource::Exif))
+    } else if modified_only {
+        modified_datetime(file_path).map(|datetime| (datetime, DateSource::Modified))
+    } else if created_only {
+        created_datetime(file_path).map(|datetime| (datetime, DateSource::Created))
+    } else if accessed_only {
+        accessed_datetime(file_path).map(|datetime| (datetime, DateSource::Accessed))
+    } else {
+        get_formatted_date(file_path, use_exiftool)
     }
 }
 
@@ -79,69 +175,266 @@ fn collect_files(dir: &Path) -> Result<Vec<std::path::PathBuf>, std::io::Error>
     Ok(files)
 }
 
+/// Stream a file through a SHA-256 digest in buffered chunks so large media
+/// files never have to be loaded into memory at once.
+fn hash_file(path: &Path) -> Result<[u8; 32], std::io::Error> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().into())
+}
+
+fn files_have_same_content(a: &Path, b: &Path) -> Result<bool, std::io::Error> {
+    Ok(hash_file(a)? == hash_file(b)?)
+}
+
+/// Stamp the EXIF capture time back onto the filesystem metadata.
+///
+/// Sets the access and modification times to the capture instant. The naive
+/// EXIF datetime is interpreted in the local timezone so it round-trips
+/// consistently with the modified-date read path, which displays stored
+/// timestamps in `Local`. Creation (birth) time is left untouched:
+/// `filetime::set_file_times` cannot write it on any platform.
+fn touch_file(file_path: &Path, datetime: NaiveDateTime) -> Result<(), std::io::Error> {
+    // Interpret the capture wall-clock time as local, matching the read path.
+    let timestamp = Local
+        .from_local_datetime(&datetime)
+        .single()
+        .map(|local| local.timestamp())
+        .unwrap_or_else(|| datetime.and_utc().timestamp());
+    let file_time = FileTime::from_unix_time(timestamp, 0);
+
+    filetime::set_file_times(file_path, file_time, file_time)?;
+
+    Ok(())
+}
+
+/// Outcome of placing a single file under its computed target name.
+enum WriteResult {
+    Renamed(PathBuf),
+    /// The target already holds a byte-identical copy, so the file was left
+    /// untouched instead of creating a numbered duplicate.
+    Skipped(PathBuf),
+}
+
 fn write_new_file_name(
     original_path: &Path,
     formatted_date: &str,
-    verbose: bool,
-) -> Result<(), std::io::Error> {
-    if let Some(parent) = original_path.parent() {
-        let mut new_file_name = format!(
-            "{}.{}",
-            formatted_date,
-            original_path
-                .extension()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_lowercase()
-        );
-        let mut new_path = parent.join(&new_file_name);
-
-        // Add counter suffix if file already exists
-        let mut counter = 1;
-        while new_path.exists() && new_path != original_path {
-            new_file_name = format!(
-                "{}-{}.{}",
-                formatted_date,
-                counter,
-                original_path
-                    .extension()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_lowercase()
-            );
-            new_path = parent.join(&new_file_name);
-            counter += 1;
+    organize_dir: Option<&Path>,
+    rename_lock: &Mutex<()>,
+) -> Result<WriteResult, std::io::Error> {
+    // When organizing, the file is moved into a library subtree (ROOT/YYYY/MM)
+    // rather than renamed in place next to the original.
+    let parent = match organize_dir {
+        Some(dir) => {
+            if dir.is_file() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!(
+                        "destination directory '{}' exists but is a file",
+                        dir.display()
+                    ),
+                ));
+            }
+            std::fs::create_dir_all(dir)?;
+            Some(dir)
         }
+        None => original_path.parent(),
+    };
+
+    if let Some(parent) = parent {
+        let extension = original_path
+            .extension()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+
+        // Walk the base name then numbered suffixes until we find a free slot.
+        // When a candidate already exists, compare contents: a byte-identical
+        // target means the file was already imported, so skip instead of
+        // spawning a duplicate; only genuinely distinct files bump the counter.
+        let mut counter = 0;
+        loop {
+            let new_file_name = if counter == 0 {
+                format!("{}.{}", formatted_date, extension)
+            } else {
+                format!("{}-{}.{}", formatted_date, counter, extension)
+            };
+            let new_path = parent.join(&new_file_name);
 
-        // Rename the file
-        std::fs::rename(original_path, &new_path)?;
+            if new_path == original_path {
+                // The file already carries its target name; nothing to do.
+                return Ok(WriteResult::Renamed(new_path));
+            }
+
+            if new_path.exists() {
+                // Hash the colliding pair WITHOUT holding the rename lock, so
+                // files landing on distinct targets still digest in parallel.
+                if files_have_same_content(original_path, &new_path)? {
+                    return Ok(WriteResult::Skipped(new_path));
+                }
+                counter += 1;
+                continue;
+            }
 
-        if verbose {
-            println!(
-                "Renamed '{}' to '{}'",
-                original_path.display(),
-                new_path.display()
-            );
+            // The slot looks free. Take the lock only to re-check and claim it
+            // atomically, so two threads can't settle on the same name.
+            let _guard = rename_lock.lock().unwrap();
+            if new_path.exists() {
+                // Lost the race for this slot; re-evaluate (it may now match).
+                continue;
+            }
+            std::fs::rename(original_path, &new_path)?;
+            return Ok(WriteResult::Renamed(new_path));
         }
+    } else {
+        Ok(WriteResult::Renamed(original_path.to_path_buf()))
     }
-    Ok(())
 }
 
-fn rename_files(
-    path: &str,
+/// Result of processing a single file, produced by the per-file worker
+/// (which performs any stamping and renaming) so the parallel driver only
+/// has to report outcomes.
+enum FileOutcome {
+    Renamed { from: PathBuf, to: PathBuf, source: DateSource },
+    Skipped { from: PathBuf, to: PathBuf, source: DateSource },
+    Touched { path: PathBuf, date: String },
+    DateOnly { path: PathBuf, date: String, source: DateSource },
+    NoDate { path: PathBuf },
+    Error { path: PathBuf, error: std::io::Error },
+}
+
+/// User-selected options threaded through the rename pipeline, bundled so the
+/// per-file worker and driver don't have to pass a dozen positional flags.
+struct Config<'a> {
     exif_only: bool,
     modified_only: bool,
-    date_format: &str,
+    created_only: bool,
+    accessed_only: bool,
+    use_exiftool: bool,
+    date_format: &'a str,
     verbose: bool,
     write: bool,
-) {
-    if verbose {
+    touch: bool,
+    organize: Option<&'a str>,
+    jobs: Option<usize>,
+}
+
+fn process_file(
+    file_path: &Path,
+    config: &Config,
+    rename_lock: &Mutex<()>,
+) -> Option<FileOutcome> {
+    if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
+        if file_name.starts_with('.') {
+            return None; // Skip hidden files like .DS_Store
+        }
+    }
+
+    // Stamp mode corrects the filesystem timestamps from the EXIF capture date.
+    // It can run on its own or alongside --write (stamp first, then rename).
+    if config.touch {
+        match parse_exif_date(file_path) {
+            Some(datetime) => {
+                if let Err(error) = touch_file(file_path, datetime) {
+                    return Some(FileOutcome::Error {
+                        path: file_path.to_path_buf(),
+                        error,
+                    });
+                }
+                if !config.write {
+                    return Some(FileOutcome::Touched {
+                        path: file_path.to_path_buf(),
+                        date: datetime.format(config.date_format).to_string(),
+                    });
+                }
+            }
+            None if !config.write => {
+                return Some(FileOutcome::NoDate {
+                    path: file_path.to_path_buf(),
+                });
+            }
+            None => {}
+        }
+    }
+
+    let (datetime, source) = match resolve_date(
+        file_path,
+        config.exif_only,
+        config.modified_only,
+        config.created_only,
+        config.accessed_only,
+        config.use_exiftool,
+    ) {
+        Some(result) => result,
+        None => {
+            return Some(FileOutcome::NoDate {
+                path: file_path.to_path_buf(),
+            })
+        }
+    };
+
+    let date = datetime.format(config.date_format).to_string();
+
+    if !config.write {
+        return Some(FileOutcome::DateOnly {
+            path: file_path.to_path_buf(),
+            date,
+            source,
+        });
+    }
+
+    // Build the ROOT/YYYY/MM destination from the already-resolved date,
+    // avoiding a second round of EXIF parsing / metadata I/O per file.
+    let organize_dir = config
+        .organize
+        .map(|root| Path::new(root).join(datetime.format("%Y/%m").to_string()));
+
+    // The rename lock is taken inside write_new_file_name around only the
+    // exists-check + rename, leaving content hashing to run in parallel.
+    match write_new_file_name(file_path, &date, organize_dir.as_deref(), rename_lock) {
+        Ok(WriteResult::Renamed(to)) => Some(FileOutcome::Renamed {
+            from: file_path.to_path_buf(),
+            to,
+            source,
+        }),
+        Ok(WriteResult::Skipped(to)) => Some(FileOutcome::Skipped {
+            from: file_path.to_path_buf(),
+            to,
+            source,
+        }),
+        Err(error) => Some(FileOutcome::Error {
+            path: file_path.to_path_buf(),
+            error,
+        }),
+    }
+}
+
+fn rename_files(path: &str, config: &Config) {
+    if config.verbose {
         println!("Path: {}", path);
-        println!("EXIF Only: {}", exif_only);
-        println!("Modified Only: {}", modified_only);
-        println!("Date Format: {}", date_format);
-        println!("Verbose: {}", verbose);
-        println!("Write: {}", write);
+        println!("EXIF Only: {}", config.exif_only);
+        println!("Modified Only: {}", config.modified_only);
+        println!("Created Only: {}", config.created_only);
+        println!("Accessed Only: {}", config.accessed_only);
+        println!("Use exiftool: {}", config.use_exiftool);
+        println!("Date Format: {}", config.date_format);
+        println!("Verbose: {}", config.verbose);
+        println!("Write: {}", config.write);
+        println!("Touch: {}", config.touch);
+        if let Some(root) = config.organize {
+            println!("Organize: {}", root);
+        }
+        if let Some(jobs) = config.jobs {
+            println!("Jobs: {}", jobs);
+        }
     }
 
     let root_path = Path::new(path);
@@ -166,39 +459,88 @@ fn rename_files(
         std::process::exit(1);
     };
 
-    // Process each file
-    for file_path in files {
-        if let Some(file_name) = file_path.file_name().and_then(|n| n.to_str()) {
-            if file_name.starts_with('.') {
-                continue; // Skip hidden files like .DS_Store
-            }
+    // Cap the thread pool when the user requested a specific job count.
+    if let Some(jobs) = config.jobs {
+        if let Err(e) = ThreadPoolBuilder::new().num_threads(jobs).build_global() {
+            eprintln!("Error configuring thread pool: {}", e);
+            std::process::exit(1);
         }
+    }
 
-        let formatted_date = if exif_only {
-            format_exif_date(&file_path, date_format)
-        } else if modified_only {
-            format_modified_date(&file_path, date_format)
-        } else {
-            get_formatted_date(&file_path, date_format)
-        };
+    // Progress bar advances once per completed file.
+    let progress = ProgressBar::new(files.len() as u64);
+    progress.set_style(
+        ProgressStyle::with_template("{bar:40} {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("##-"),
+    );
 
-        if let Some(date) = formatted_date {
-            if write {
-                if let Err(e) = write_new_file_name(&file_path, &date, verbose) {
-                    eprintln!("Error renaming file '{}': {}", file_path.display(), e);
+    // EXIF parsing is blocking I/O per file, so fan the work out across cores.
+    // A shared mutex serializes only the rename/collision step.
+    let rename_lock = Mutex::new(());
+    files.par_iter().for_each(|file_path| {
+        if let Some(outcome) = process_file(file_path, config, &rename_lock) {
+            match outcome {
+                FileOutcome::Renamed { from, to, source } => {
+                    if config.verbose {
+                        progress.println(format!(
+                            "Renamed '{}' to '{}' ({})",
+                            from.display(),
+                            to.display(),
+                            source.label()
+                        ));
+                    }
+                }
+                FileOutcome::Skipped { from, to, source } => {
+                    if config.verbose {
+                        progress.println(format!(
+                            "Skipped '{}': identical content already at '{}' ({})",
+                            from.display(),
+                            to.display(),
+                            source.label()
+                        ));
+                    }
+                }
+                FileOutcome::Touched { path, date } => {
+                    if config.verbose {
+                        progress.println(format!(
+                            "Stamped '{}' with capture date {}",
+                            path.display(),
+                            date
+                        ));
+                    }
+                }
+                FileOutcome::DateOnly { path, date, source } => {
+                    if config.verbose {
+                        progress.println(format!(
+                            "Date for '{}': {} ({})",
+                            path.display(),
+                            date,
+                            source.label()
+                        ));
+                    }
+                }
+                FileOutcome::NoDate { path } => {
+                    if config.verbose {
+                        progress.println(format!(
+                            "No date information available for '{}'.",
+                            path.display()
+                        ));
+                    }
+                }
+                FileOutcome::Error { path, error } => {
+                    progress.println(format!(
+                        "Error processing file '{}': {}",
+                        path.display(),
+                        error
+                    ));
                 }
-            } else if verbose {
-                println!("Date for '{}': {}", file_path.display(), date);
-            }
-        } else {
-            if verbose {
-                println!(
-                    "No date information available for '{}'.",
-                    file_path.display()
-                );
             }
         }
-    }
+        progress.inc(1);
+    });
+
+    progress.finish_and_clear();
 }
 
 fn main() {
@@ -226,6 +568,20 @@ fn main() {
                 .help("Only use modified date for renaming (cannot be used with --exif)")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("created_only")
+                .short('c')
+                .long("created")
+                .help("Only use created date for renaming (cannot be used with other date-source flags)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("accessed_only")
+                .short('a')
+                .long("accessed")
+                .help("Only use accessed date for renaming (cannot be used with other date-source flags)")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(
             Arg::new("date_format")
                 .short('f')
@@ -247,9 +603,42 @@ fn main() {
                 .help("Rename files to the parsed date")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("touch")
+                .short('t')
+                .long("touch")
+                .help("Set the access and modification times to the EXIF capture date (can combine with --write)")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("use_exiftool")
+                .long("use-exiftool")
+                .help("Fall back to the external exiftool binary for formats the built-in reader can't parse")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("organize")
+                .short('o')
+                .long("organize")
+                .value_name("ROOT")
+                .help("Move files into a ROOT/YYYY/MM library tree instead of renaming in place"),
+        )
+        .arg(
+            Arg::new("jobs")
+                .short('j')
+                .long("jobs")
+                .value_name("N")
+                .value_parser(clap::value_parser!(usize))
+                .help("Cap the number of worker threads (defaults to the number of cores)"),
+        )
         .group(
             clap::ArgGroup::new("date_source")
-                .args(&["exif_only", "modified_only"])
+                .args([
+                    "exif_only",
+                    "modified_only",
+                    "created_only",
+                    "accessed_only",
+                ])
                 .multiple(false),
         )
         .get_matches();
@@ -257,9 +646,29 @@ fn main() {
     let path = matches.get_one::<String>("path").unwrap();
     let exif_only = matches.get_flag("exif_only");
     let modified_only = matches.get_flag("modified_only");
+    let created_only = matches.get_flag("created_only");
+    let accessed_only = matches.get_flag("accessed_only");
     let date_format = matches.get_one::<String>("date_format").unwrap();
     let verbose = matches.get_flag("verbose");
     let write = matches.get_flag("write");
+    let touch = matches.get_flag("touch");
+    let use_exiftool = matches.get_flag("use_exiftool");
+    let organize = matches.get_one::<String>("organize").map(|s| s.as_str());
+    let jobs = matches.get_one::<usize>("jobs").copied();
+
+    let config = Config {
+        exif_only,
+        modified_only,
+        created_only,
+        accessed_only,
+        use_exiftool,
+        date_format,
+        verbose,
+        write,
+        touch,
+        organize,
+        jobs,
+    };
 
-    rename_files(path, exif_only, modified_only, date_format, verbose, write);
+    rename_files(path, &config);
 }